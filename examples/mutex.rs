@@ -6,8 +6,8 @@ use core::{
 };
 use std::{
     sync::Arc,
-    thread::{current, park, sleep, Builder, Thread},
-    time::Duration,
+    thread::{current, park, park_timeout, sleep, Builder, Thread},
+    time::{Duration, Instant},
 };
 
 use pinned_init::*;
@@ -53,6 +53,7 @@ pub struct Mutex<T> {
     wait_list: ListHead,
     spin_lock: SpinLock,
     locked: Cell<bool>,
+    poisoned: AtomicBool,
     data: UnsafeCell<T>,
 }
 
@@ -63,12 +64,13 @@ impl<T> Mutex<T> {
             wait_list: ListHead::new(),
             spin_lock: SpinLock::new(),
             locked: Cell::new(false),
+            poisoned: AtomicBool::new(false),
             data: UnsafeCell::new(val),
         })
     }
 
     #[inline]
-    pub fn lock(&self) -> MutexGuard<'_, T> {
+    pub fn lock(&self) -> LockResult<MutexGuard<'_, T>> {
         let mut sguard = self.spin_lock.acquire();
         if self.locked.get() {
             stack_init!(let wait_entry = WaitEntry::insert_new(&self.wait_list));
@@ -76,15 +78,83 @@ impl<T> Mutex<T> {
                 Ok(w) => w,
                 Err(e) => match e {},
             };
-            while self.locked.get() {
+            // The releaser hands the lock directly to the head waiter instead
+            // of merely clearing `locked`, so we never re-contend here: once
+            // `wake` is set we already own the lock.
+            while !wait_entry.wake.get() {
                 drop(sguard);
                 park();
                 sguard = self.spin_lock.acquire();
             }
             drop(wait_entry);
+            drop(sguard);
+            let guard = MutexGuard { mtx: self };
+            return if self.poisoned.load(Ordering::Acquire) {
+                Err(PoisonError::new(guard))
+            } else {
+                Ok(guard)
+            };
+        }
+        self.locked.set(true);
+        drop(sguard);
+        let guard = MutexGuard { mtx: self };
+        if self.poisoned.load(Ordering::Acquire) {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// Acquires the lock without blocking, returning `None` if it is already
+    /// held instead of queuing onto the wait list.
+    #[inline]
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        let sguard = self.spin_lock.acquire();
+        if self.locked.get() {
+            None
+        } else {
+            self.locked.set(true);
+            Some(MutexGuard { mtx: self })
+        }
+    }
+
+    /// Like [`lock`](Self::lock), but gives up and returns `None` if `dur`
+    /// elapses before the lock is acquired. The wait entry is unlinked from
+    /// the wait list under the spin lock before giving up, so a later release
+    /// never unparks an abandoned stack frame.
+    #[inline]
+    pub fn lock_timeout(&self, dur: Duration) -> Option<MutexGuard<'_, T>> {
+        let mut sguard = self.spin_lock.acquire();
+        if self.locked.get() {
+            stack_init!(let wait_entry = WaitEntry::insert_new(&self.wait_list));
+            let wait_entry = match wait_entry {
+                Ok(w) => w,
+                Err(e) => match e {},
+            };
+            let deadline = Instant::now() + dur;
+            while !wait_entry.wake.get() {
+                drop(sguard);
+                match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) => park_timeout(remaining),
+                    None => {
+                        sguard = self.spin_lock.acquire();
+                        if !wait_entry.wake.get() {
+                            drop(wait_entry);
+                            drop(sguard);
+                            return None;
+                        }
+                        break;
+                    }
+                }
+                sguard = self.spin_lock.acquire();
+            }
+            drop(wait_entry);
+            drop(sguard);
+            return Some(MutexGuard { mtx: self });
         }
         self.locked.set(true);
-        MutexGuard { mtx: self }
+        drop(sguard);
+        Some(MutexGuard { mtx: self })
     }
 }
 
@@ -99,15 +169,47 @@ impl<'a, T> Drop for MutexGuard<'a, T> {
     #[inline]
     fn drop(&mut self) {
         let sguard = self.mtx.spin_lock.acquire();
-        self.mtx.locked.set(false);
-        if let Some(list_field) = self.mtx.wait_list.next() {
-            let wait_entry = list_field.as_ptr().cast::<WaitEntry>();
-            unsafe { (*wait_entry).thread.unpark() };
+        if std::thread::panicking() {
+            self.mtx.poisoned.store(true, Ordering::Release);
+        }
+        // Hand the lock directly to the head waiter, if any, instead of
+        // clearing `locked` and letting every parked thread race for it:
+        // `locked` stays true and only that one thread is ever unparked.
+        match self.mtx.wait_list.next() {
+            Some(list_field) => {
+                let wait_entry = list_field.as_ptr().cast::<WaitEntry>();
+                unsafe {
+                    (*wait_entry).wake.set(true);
+                    (*wait_entry).thread.unpark();
+                }
+            }
+            None => self.mtx.locked.set(false),
         }
         drop(sguard);
     }
 }
 
+/// A [`Mutex`] whose data may have been left in an inconsistent state by a
+/// thread that panicked while holding the lock. Call
+/// [`into_inner`](PoisonError::into_inner) to recover the guard anyway.
+pub struct PoisonError<G> {
+    guard: G,
+}
+
+impl<G> PoisonError<G> {
+    #[inline]
+    fn new(guard: G) -> Self {
+        Self { guard }
+    }
+
+    #[inline]
+    pub fn into_inner(self) -> G {
+        self.guard
+    }
+}
+
+pub type LockResult<G> = Result<G, PoisonError<G>>;
+
 impl<'a, T> Deref for MutexGuard<'a, T> {
     type Target = T;
 
@@ -130,6 +232,9 @@ struct WaitEntry {
     #[pin]
     wait_list: ListHead,
     thread: Thread,
+    /// Set by the releaser once the lock has been handed directly to this
+    /// waiter, so it can tell a real wakeup apart from a spurious one.
+    wake: Cell<bool>,
 }
 
 impl WaitEntry {
@@ -138,8 +243,327 @@ impl WaitEntry {
         pin_init!(Self {
             thread: current(),
             wait_list: ListHead::insert_prev(list),
+            wake: Cell::new(false),
+        })
+    }
+}
+
+/// A condition variable that parks waiters on its own intrusive wait list and
+/// wakes them back up once [`notify_one`](Condvar::notify_one) or
+/// [`notify_all`](Condvar::notify_all) is called.
+#[pin_project]
+pub struct Condvar {
+    #[pin]
+    wait_list: ListHead,
+    spin_lock: SpinLock,
+}
+
+impl Condvar {
+    #[inline]
+    pub fn new() -> impl PinInit<Self> {
+        pin_init!(Self {
+            wait_list: ListHead::new(),
+            spin_lock: SpinLock::new(),
+        })
+    }
+
+    /// Atomically releases `guard` and parks the current thread, re-acquiring
+    /// the mutex before returning. The wait entry is queued before the mutex
+    /// is released, so a `notify` racing with the start of this call can never
+    /// be missed.
+    #[inline]
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> LockResult<MutexGuard<'a, T>> {
+        let mtx = guard.mtx;
+        let sguard = self.spin_lock.acquire();
+        stack_init!(let wait_entry = CondvarWaitEntry::insert_new(&self.wait_list));
+        let wait_entry = match wait_entry {
+            Ok(w) => w,
+            Err(e) => match e {},
+        };
+        drop(sguard);
+        drop(guard);
+        while !wait_entry.wake.load(Ordering::Acquire) {
+            park();
+        }
+        let sguard = self.spin_lock.acquire();
+        drop(wait_entry);
+        drop(sguard);
+        mtx.lock()
+    }
+
+    /// Wakes a single waiting thread, if any. The entry stays linked until the
+    /// woken thread itself drops it in `wait`, same as the Mutex wait list, so
+    /// entries that were already notified are skipped.
+    #[inline]
+    pub fn notify_one(&self) {
+        let sguard = self.spin_lock.acquire();
+        let mut cur = self.wait_list.next();
+        while let Some(node) = cur {
+            if core::ptr::eq(node.as_ptr(), &self.wait_list) {
+                break;
+            }
+            let entry = node.as_ptr().cast::<CondvarWaitEntry>();
+            if !unsafe { (*entry).wake.load(Ordering::Relaxed) } {
+                unsafe {
+                    (*entry).wake.store(true, Ordering::Release);
+                    (*entry).thread.unpark();
+                }
+                break;
+            }
+            cur = unsafe { node.as_ref() }.next();
+        }
+        drop(sguard);
+    }
+
+    /// Wakes every waiting thread.
+    #[inline]
+    pub fn notify_all(&self) {
+        let sguard = self.spin_lock.acquire();
+        let mut cur = self.wait_list.next();
+        while let Some(node) = cur {
+            if core::ptr::eq(node.as_ptr(), &self.wait_list) {
+                break;
+            }
+            cur = unsafe { node.as_ref() }.next();
+            let entry = node.as_ptr().cast::<CondvarWaitEntry>();
+            unsafe {
+                (*entry).wake.store(true, Ordering::Release);
+                (*entry).thread.unpark();
+            }
+        }
+        drop(sguard);
+    }
+}
+
+unsafe impl Send for Condvar {}
+unsafe impl Sync for Condvar {}
+
+/// A `Mutex` paired with the `Condvar` that guards it, demonstrating how the
+/// two are meant to be pinned and initialized together.
+#[pin_project]
+struct Pair {
+    #[pin]
+    mtx: Mutex<bool>,
+    #[pin]
+    cv: Condvar,
+}
+
+impl Pair {
+    #[inline]
+    fn new() -> impl PinInit<Self> {
+        pin_init!(Self {
+            mtx: Mutex::new(false),
+            cv: Condvar::new(),
+        })
+    }
+}
+
+#[pin_project]
+#[repr(C)]
+struct CondvarWaitEntry {
+    #[pin]
+    wait_list: ListHead,
+    thread: Thread,
+    /// Atomic because `notify_*` sets it and a waiter reads it with no spin
+    /// lock held in between (unlike the Mutex's `WaitEntry`).
+    wake: AtomicBool,
+}
+
+impl CondvarWaitEntry {
+    #[inline]
+    fn insert_new(list: &ListHead) -> impl PinInit<Self> + '_ {
+        pin_init!(Self {
+            thread: current(),
+            wait_list: ListHead::insert_prev(list),
+            wake: AtomicBool::new(false),
+        })
+    }
+}
+
+/// Distinguishes the two kinds of waiter that can sit on an [`RwLock`]'s wait
+/// list, so the release path knows whether to wake one thread or a run of them.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum WaitKind {
+    Read,
+    Write,
+}
+
+#[pin_project]
+#[repr(C)]
+struct RwWaitEntry {
+    #[pin]
+    wait_list: ListHead,
+    thread: Thread,
+    kind: WaitKind,
+}
+
+impl RwWaitEntry {
+    #[inline]
+    fn insert_new(list: &ListHead, kind: WaitKind) -> impl PinInit<Self> + '_ {
+        pin_init!(Self {
+            thread: current(),
+            wait_list: ListHead::insert_prev(list),
+            kind,
+        })
+    }
+}
+
+/// A reader-writer lock built on the same intrusive wait list and spin lock as
+/// [`Mutex`]. `state` is `0` when free, `> 0` while that many readers hold the
+/// lock, and `-1` while a writer holds it.
+#[pin_project]
+pub struct RwLock<T> {
+    #[pin]
+    wait_list: ListHead,
+    spin_lock: SpinLock,
+    state: Cell<isize>,
+    waiting_writers: Cell<usize>,
+    data: UnsafeCell<T>,
+}
+
+impl<T> RwLock<T> {
+    #[inline]
+    pub fn new(val: T) -> impl PinInit<Self> {
+        pin_init!(Self {
+            wait_list: ListHead::new(),
+            spin_lock: SpinLock::new(),
+            state: Cell::new(0),
+            waiting_writers: Cell::new(0),
+            data: UnsafeCell::new(val),
         })
     }
+
+    #[inline]
+    pub fn read(&self) -> RwLockReadGuard<'_, T> {
+        let mut sguard = self.spin_lock.acquire();
+        if self.state.get() < 0 || self.waiting_writers.get() > 0 {
+            stack_init!(let wait_entry = RwWaitEntry::insert_new(&self.wait_list, WaitKind::Read));
+            let wait_entry = match wait_entry {
+                Ok(w) => w,
+                Err(e) => match e {},
+            };
+            // Once queued, only re-check `state`: `release_waiters` already
+            // stops the woken leading run of readers at the first queued
+            // writer, so gating on `waiting_writers` here too
+            // would make a woken reader re-park behind a writer that queued
+            // after it and deadlock forever.
+            while self.state.get() < 0 {
+                drop(sguard);
+                park();
+                sguard = self.spin_lock.acquire();
+            }
+            drop(wait_entry);
+        }
+        self.state.set(self.state.get() + 1);
+        RwLockReadGuard { lock: self }
+    }
+
+    #[inline]
+    pub fn write(&self) -> RwLockWriteGuard<'_, T> {
+        let mut sguard = self.spin_lock.acquire();
+        if self.state.get() != 0 {
+            self.waiting_writers.set(self.waiting_writers.get() + 1);
+            stack_init!(let wait_entry = RwWaitEntry::insert_new(&self.wait_list, WaitKind::Write));
+            let wait_entry = match wait_entry {
+                Ok(w) => w,
+                Err(e) => match e {},
+            };
+            while self.state.get() != 0 {
+                drop(sguard);
+                park();
+                sguard = self.spin_lock.acquire();
+            }
+            drop(wait_entry);
+            self.waiting_writers.set(self.waiting_writers.get() - 1);
+        }
+        self.state.set(-1);
+        RwLockWriteGuard { lock: self }
+    }
+
+    /// Wakes the next waiter(s) once the lock has become free: the single
+    /// writer at the head of the queue, or the whole leading run of readers.
+    #[inline]
+    fn release_waiters(&self) {
+        let Some(head) = self.wait_list.next() else {
+            return;
+        };
+        let entry = head.as_ptr().cast::<RwWaitEntry>();
+        match unsafe { (*entry).kind } {
+            WaitKind::Write => unsafe { (*entry).thread.unpark() },
+            WaitKind::Read => {
+                let mut cur = Some(head);
+                while let Some(node) = cur {
+                    if core::ptr::eq(node.as_ptr(), &self.wait_list) {
+                        break;
+                    }
+                    let entry = node.as_ptr().cast::<RwWaitEntry>();
+                    if unsafe { (*entry).kind } != WaitKind::Read {
+                        break;
+                    }
+                    cur = unsafe { node.as_ref() }.next();
+                    unsafe { (*entry).thread.unpark() };
+                }
+            }
+        }
+    }
+}
+
+unsafe impl<T: Send> Send for RwLock<T> {}
+unsafe impl<T: Send + Sync> Sync for RwLock<T> {}
+
+pub struct RwLockReadGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> Drop for RwLockReadGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        let sguard = self.lock.spin_lock.acquire();
+        self.lock.state.set(self.lock.state.get() - 1);
+        if self.lock.state.get() == 0 {
+            self.lock.release_waiters();
+        }
+        drop(sguard);
+    }
+}
+
+impl<'a, T> Deref for RwLockReadGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+pub struct RwLockWriteGuard<'a, T> {
+    lock: &'a RwLock<T>,
+}
+
+impl<'a, T> Drop for RwLockWriteGuard<'a, T> {
+    #[inline]
+    fn drop(&mut self) {
+        let sguard = self.lock.spin_lock.acquire();
+        self.lock.state.set(0);
+        self.lock.release_waiters();
+        drop(sguard);
+    }
+}
+
+impl<'a, T> Deref for RwLockWriteGuard<'a, T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<'a, T> DerefMut for RwLockWriteGuard<'a, T> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        unsafe { &mut *self.lock.data.get() }
+    }
 }
 
 fn main() {
@@ -154,12 +578,12 @@ fn main() {
                 .name(format!("worker #{i}"))
                 .spawn(move || {
                     for _ in 0..workload {
-                        *mtx.lock() += 1;
+                        *mtx.lock().unwrap() += 1;
                     }
                     println!("{i} halfway");
                     sleep(Duration::from_millis((i as u64) * 10));
                     for _ in 0..workload {
-                        *mtx.lock() += 1;
+                        *mtx.lock().unwrap() += 1;
                     }
                     println!("{i} finished");
                 })
@@ -169,6 +593,75 @@ fn main() {
     for h in handles {
         h.join().expect("thread paniced");
     }
-    println!("{:?}", &*mtx.lock());
-    assert_eq!(*mtx.lock(), workload * thread_count * 2);
+    println!("{:?}", &*mtx.lock().unwrap());
+    assert_eq!(*mtx.lock().unwrap(), workload * thread_count * 2);
+
+    let rw: Pin<Arc<RwLock<usize>>> = Arc::pin_init(RwLock::new(0)).unwrap();
+    {
+        let mut w = rw.write();
+        *w += 1;
+    }
+    let mut reader_handles = vec![];
+    for i in 0..4 {
+        let rw = rw.clone();
+        reader_handles.push(
+            Builder::new()
+                .name(format!("reader #{i}"))
+                .spawn(move || *rw.read())
+                .expect("should not fail"),
+        );
+    }
+    for h in reader_handles {
+        assert_eq!(h.join().expect("thread paniced"), 1);
+    }
+    println!("rwlock: {}", *rw.read());
+
+    let pair: Pin<Arc<Pair>> = Arc::pin_init(Pair::new()).unwrap();
+    let notifier = {
+        let pair = pair.clone();
+        Builder::new()
+            .name("notifier".to_string())
+            .spawn(move || {
+                sleep(Duration::from_millis(10));
+                *pair.mtx.lock().unwrap() = true;
+                pair.cv.notify_one();
+            })
+            .expect("should not fail")
+    };
+    let mut ready = pair.mtx.lock().unwrap();
+    while !*ready {
+        ready = pair.cv.wait(ready).unwrap();
+    }
+    drop(ready);
+    notifier.join().expect("thread paniced");
+    println!("condvar: notified");
+
+    let poison_mtx: Pin<Arc<Mutex<usize>>> = Arc::pin_init(Mutex::new(0)).unwrap();
+    {
+        let poison_mtx = poison_mtx.clone();
+        Builder::new()
+            .name("panicker".to_string())
+            .spawn(move || {
+                let _guard = poison_mtx.lock().unwrap();
+                panic!("intentionally poisoning the mutex");
+            })
+            .expect("should not fail")
+            .join()
+            .expect_err("thread should have paniced");
+    }
+    match poison_mtx.lock() {
+        Ok(_) => panic!("lock should have been poisoned"),
+        Err(e) => println!("poisoned: recovered {}", *e.into_inner()),
+    }
+
+    let timeout_mtx: Pin<Arc<Mutex<usize>>> = Arc::pin_init(Mutex::new(0)).unwrap();
+    {
+        let _held = timeout_mtx.lock().unwrap();
+        assert!(timeout_mtx.try_lock().is_none());
+        assert!(timeout_mtx
+            .lock_timeout(Duration::from_millis(10))
+            .is_none());
+    }
+    assert!(timeout_mtx.try_lock().is_some());
+    println!("try_lock/lock_timeout: ok");
 }